@@ -0,0 +1,361 @@
+/// Resolves `after`/`until` task references into concrete calendar dates.
+///
+/// Mermaid gantt tasks are positionally sequential: a task with no start field
+/// inherits the previous task's end date, a start field of `after <id> [<id>...]`
+/// resolves to the latest end date among the referenced tasks, and an end field
+/// is either a literal date, a duration (`30d`, `2w`) added to the start, or
+/// `until <id>` which pins the end to another task's start.
+///
+/// This module walks tasks in document order and builds a map of task id to
+/// its resolved `(start, end)` `NaiveDate` pair, so callers (e.g. the
+/// `--resolve` flag in `main`, and the HTML renderer) don't have to re-derive
+/// dates from the raw text themselves.
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::split_meta_tags;
+use crate::TASK_TAGS;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateResolutionError {
+    /// A literal date or the `dateFormat` keyword line didn't parse.
+    InvalidDate { value: String, format: String },
+    /// A duration token (e.g. `30d`) wasn't `<number><d|w>`.
+    InvalidDuration(String),
+    /// `after`/`until` referenced an id that never appears as a task id.
+    UnknownTaskId(String),
+    /// `after`/`until` referenced an id whose own dates haven't been resolved
+    /// yet (i.e. it is declared later in the file).
+    NotYetResolved(String),
+}
+
+impl fmt::Display for DateResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateResolutionError::InvalidDate { value, format } => {
+                write!(f, "could not parse date '{value}' with dateFormat '{format}'")
+            }
+            DateResolutionError::InvalidDuration(value) => {
+                write!(f, "could not parse duration '{value}', expected e.g. '30d' or '2w'")
+            }
+            DateResolutionError::UnknownTaskId(id) => {
+                write!(f, "reference to unknown task id '{id}'")
+            }
+            DateResolutionError::NotYetResolved(id) => {
+                write!(f, "task id '{id}' is referenced before its own dates are resolved")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DateResolutionError {}
+
+/// A task's start/end, once resolved to concrete calendar dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedDates {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// The result of [`resolve_task_dates_in_order`]: every task's dates in
+/// document order (`None` for lines that can't be dated), plus the same
+/// dates keyed by id for `after`/`until` lookups.
+type OrderedResolution = (Vec<Option<ResolvedDates>>, HashMap<String, ResolvedDates>);
+
+/// Days of the week and literal dates passed to the `excludes` keyword.
+#[derive(Debug, Clone, Default)]
+pub struct Excludes {
+    pub weekdays: Vec<Weekday>,
+    pub dates: Vec<NaiveDate>,
+}
+
+impl Excludes {
+    fn is_excluded(&self, date: NaiveDate) -> bool {
+        self.weekdays.contains(&date.weekday()) || self.dates.contains(&date)
+    }
+}
+
+/// Maps a Mermaid `dateFormat` token (e.g. `YYYY-MM-DD`) to a chrono format
+/// string (e.g. `%Y-%m-%d`). Unknown characters are passed through as-is.
+pub fn mermaid_date_format_to_chrono(date_format: &str) -> String {
+    let mut chrono_format = String::with_capacity(date_format.len());
+    let chars: Vec<char> = date_format.chars().collect();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let remaining: String = chars[idx..].iter().collect();
+        if remaining.starts_with("YYYY") {
+            chrono_format.push_str("%Y");
+            idx += 4;
+        } else if remaining.starts_with("MM") {
+            chrono_format.push_str("%m");
+            idx += 2;
+        } else if remaining.starts_with("DD") {
+            chrono_format.push_str("%d");
+            idx += 2;
+        } else if remaining.starts_with("HH") {
+            chrono_format.push_str("%H");
+            idx += 2;
+        } else if remaining.starts_with("mm") {
+            chrono_format.push_str("%M");
+            idx += 2;
+        } else if remaining.starts_with("ss") {
+            chrono_format.push_str("%S");
+            idx += 2;
+        } else {
+            chrono_format.push(chars[idx]);
+            idx += 1;
+        }
+    }
+    chrono_format
+}
+
+/// Finds the `dateFormat` keyword line and returns its chrono equivalent,
+/// defaulting to `%Y-%m-%d` (Mermaid's own default of `YYYY-MM-DD`).
+pub fn resolve_date_format(lines: &[&str]) -> String {
+    for line in lines.iter().map(|l| l.trim()) {
+        if let Some(value) = line.strip_prefix("dateFormat") {
+            return mermaid_date_format_to_chrono(value.trim());
+        }
+    }
+    mermaid_date_format_to_chrono("YYYY-MM-DD")
+}
+
+/// Finds the `excludes` keyword line (e.g. `excludes weekends 2014-01-10`)
+/// and parses it into weekdays and literal dates. `weekends` expands to
+/// Saturday and Sunday, matching Mermaid's own shorthand.
+pub fn resolve_excludes(lines: &[&str], date_format: &str) -> Excludes {
+    let mut excludes = Excludes::default();
+    for line in lines.iter().map(|l| l.trim()) {
+        if let Some(value) = line.strip_prefix("excludes") {
+            for token in value.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+                match token {
+                    "weekends" => {
+                        excludes.weekdays.push(Weekday::Sat);
+                        excludes.weekdays.push(Weekday::Sun);
+                    }
+                    "monday" | "mon" => excludes.weekdays.push(Weekday::Mon),
+                    "tuesday" | "tue" => excludes.weekdays.push(Weekday::Tue),
+                    "wednesday" | "wed" => excludes.weekdays.push(Weekday::Wed),
+                    "thursday" | "thu" => excludes.weekdays.push(Weekday::Thu),
+                    "friday" | "fri" => excludes.weekdays.push(Weekday::Fri),
+                    "saturday" | "sat" => excludes.weekdays.push(Weekday::Sat),
+                    "sunday" | "sun" => excludes.weekdays.push(Weekday::Sun),
+                    _ => {
+                        if let Ok(date) = NaiveDate::parse_from_str(token, date_format) {
+                            excludes.dates.push(date);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    excludes
+}
+
+fn is_duration_token(token: &str) -> bool {
+    let token = token.trim();
+    matches!(token.strip_suffix(['d', 'w']), Some(count) if count.parse::<i64>().is_ok())
+}
+
+/// Adds a `<number>d`/`<number>w` duration token to `start`, skipping any
+/// excluded day so the resulting span covers the requested number of
+/// working days, not calendar days.
+fn add_duration(start: NaiveDate, duration: &str, excludes: &Excludes) -> Result<NaiveDate, DateResolutionError> {
+    let duration = duration.trim();
+    let (count_str, unit) = duration.split_at(duration.len().saturating_sub(1));
+    let count: i64 = count_str
+        .parse()
+        .map_err(|_| DateResolutionError::InvalidDuration(duration.to_string()))?;
+    let days = match unit {
+        "d" => count,
+        "w" => count * 7,
+        _ => return Err(DateResolutionError::InvalidDuration(duration.to_string())),
+    };
+
+    let mut date = start;
+    let mut remaining = days;
+    let step = if remaining < 0 { -1 } else { 1 };
+    while remaining != 0 {
+        date += Duration::days(step);
+        if !excludes.is_excluded(date) {
+            remaining -= step;
+        }
+    }
+    Ok(date)
+}
+
+/// Formats a resolved date back into the document's `dateFormat`, the
+/// inverse of [`parse_literal_date`].
+pub fn format_date(date: NaiveDate, chrono_date_format: &str) -> String {
+    date.format(chrono_date_format).to_string()
+}
+
+fn parse_literal_date(value: &str, date_format: &str) -> Result<NaiveDate, DateResolutionError> {
+    NaiveDate::parse_from_str(value.trim(), date_format).map_err(|_| DateResolutionError::InvalidDate {
+        value: value.trim().to_string(),
+        format: date_format.to_string(),
+    })
+}
+
+/// One task's id (if present) and its raw start/end fields, in document order.
+struct RawTask<'a> {
+    id: Option<&'a str>,
+    start: Option<&'a str>,
+    end: &'a str,
+}
+
+/// Parses each `task_lines` entry into a `RawTask`, one slot per line (in
+/// the same order and count as `task_lines`). A line with 0 or 4+ UDI
+/// fields (malformed but still parseable as a task line, e.g. `Foo :` or
+/// `Foo :milestone`) can't be dated, so its slot is `None` rather than being
+/// dropped — keeping this 1:1 with `task_lines` is what lets callers zip
+/// the two back together positionally instead of drifting out of sync.
+fn raw_tasks_in_order<'a>(task_lines: &[&'a str]) -> Vec<Option<RawTask<'a>>> {
+    let mut tasks = vec![];
+    for line in task_lines {
+        let task_split: Vec<&str> = line.split(':').map(str::trim).collect();
+        if task_split.len() < 2 {
+            tasks.push(None);
+            continue;
+        }
+        let meta_items = split_meta_tags(TASK_TAGS, task_split[1]);
+        let task_udis = meta_items.get("udis").unwrap();
+        let task = match task_udis.len() {
+            3 => Some(RawTask { id: Some(task_udis[0]), start: Some(task_udis[1]), end: task_udis[2] }),
+            2 => Some(RawTask { id: None, start: Some(task_udis[0]), end: task_udis[1] }),
+            1 => Some(RawTask { id: None, start: None, end: task_udis[0] }),
+            _ => None,
+        };
+        tasks.push(task);
+    }
+    tasks
+}
+
+/// Resolves every task's concrete `(start, end)` date, returning both the
+/// dates in document order — one slot per line returned by `get_task_lines`,
+/// `None` where the line has too few/many UDI fields to date at all, so
+/// callers can zip this back up against `get_task_lines` positionally
+/// without drifting out of sync on malformed-but-parseable lines — and the
+/// same dates keyed by task id, for resolving `after`/`until` references.
+pub fn resolve_task_dates_in_order(lines: Vec<&str>) -> Result<OrderedResolution, DateResolutionError> {
+    let date_format = resolve_date_format(&lines);
+    let excludes = resolve_excludes(&lines, &date_format);
+    let task_lines = crate::get_task_lines(lines);
+    let raw_tasks = raw_tasks_in_order(&task_lines);
+    let all_ids: Vec<&str> = raw_tasks.iter().flatten().filter_map(|t| t.id).collect();
+
+    let lookup = |resolved: &HashMap<String, ResolvedDates>, ref_id: &str| -> Result<ResolvedDates, DateResolutionError> {
+        match resolved.get(ref_id) {
+            Some(dates) => Ok(*dates),
+            None if all_ids.contains(&ref_id) => Err(DateResolutionError::NotYetResolved(ref_id.to_string())),
+            None => Err(DateResolutionError::UnknownTaskId(ref_id.to_string())),
+        }
+    };
+
+    let resolve_after = |resolved: &HashMap<String, ResolvedDates>, ids: &str| -> Result<NaiveDate, DateResolutionError> {
+        let mut latest: Option<NaiveDate> = None;
+        for ref_id in ids.split_whitespace() {
+            let dates = lookup(resolved, ref_id)?;
+            latest = Some(match latest {
+                Some(current) if current >= dates.end => current,
+                _ => dates.end,
+            });
+        }
+        latest.ok_or_else(|| DateResolutionError::UnknownTaskId(ids.to_string()))
+    };
+
+    let mut resolved: HashMap<String, ResolvedDates> = HashMap::new();
+    let mut in_order: Vec<Option<ResolvedDates>> = Vec::with_capacity(raw_tasks.len());
+    let mut previous_end: Option<NaiveDate> = None;
+
+    for maybe_task in &raw_tasks {
+        let Some(task) = maybe_task else {
+            // Malformed line (0 or 4+ UDI fields): can't be dated, and
+            // doesn't advance the "previous task's end" sequencing rule.
+            in_order.push(None);
+            continue;
+        };
+
+        // A milestone (or any task) with no start field and an `after <id>`
+        // end field is a single point in time: the sole field resolves like
+        // a start reference, and start == end (zero-duration).
+        if task.start.is_none() {
+            if let Some(ids) = task.end.trim().strip_prefix("after ") {
+                let point = resolve_after(&resolved, ids)?;
+                previous_end = Some(point);
+                let dates = ResolvedDates { start: point, end: point };
+                in_order.push(Some(dates));
+                if let Some(id) = task.id {
+                    resolved.insert(id.to_string(), dates);
+                }
+                continue;
+            }
+        }
+
+        let start = match task.start {
+            Some(start_field) if start_field.starts_with("after ") => {
+                resolve_after(&resolved, start_field.strip_prefix("after ").unwrap())?
+            }
+            Some(literal) => parse_literal_date(literal, &date_format)?,
+            None => previous_end.ok_or_else(|| DateResolutionError::InvalidDate {
+                value: task.end.to_string(),
+                format: date_format.clone(),
+            })?,
+        };
+
+        let end = if let Some(ref_id) = task.end.trim().strip_prefix("until ") {
+            lookup(&resolved, ref_id.trim())?.start
+        } else if is_duration_token(task.end) {
+            add_duration(start, task.end, &excludes)?
+        } else {
+            parse_literal_date(task.end, &date_format)?
+        };
+
+        previous_end = Some(end);
+        let dates = ResolvedDates { start, end };
+        in_order.push(Some(dates));
+        if let Some(id) = task.id {
+            resolved.insert(id.to_string(), dates);
+        }
+    }
+
+    Ok((in_order, resolved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn add_duration_skips_excluded_weekdays() {
+        let excludes = Excludes { weekdays: vec![Weekday::Sat, Weekday::Sun], dates: vec![] };
+        // 2024-01-01 is a Monday; +5 working days lands on the next Monday,
+        // skipping the Saturday/Sunday in between.
+        let end = add_duration(date(2024, 1, 1), "5d", &excludes).unwrap();
+        assert_eq!(end, date(2024, 1, 8));
+    }
+
+    #[test]
+    fn add_duration_skips_excluded_literal_dates() {
+        let excludes = Excludes { weekdays: vec![], dates: vec![date(2024, 1, 2)] };
+        let end = add_duration(date(2024, 1, 1), "2d", &excludes).unwrap();
+        assert_eq!(end, date(2024, 1, 4));
+    }
+
+    #[test]
+    fn add_duration_weeks_count_calendar_weeks() {
+        let end = add_duration(date(2024, 1, 1), "2w", &Excludes::default()).unwrap();
+        assert_eq!(end, date(2024, 1, 15));
+    }
+
+    #[test]
+    fn add_duration_rejects_malformed_token() {
+        assert!(add_duration(date(2024, 1, 1), "abc", &Excludes::default()).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+/// Detects, parses, and round-trips a leading YAML frontmatter block
+/// (`--- ... ---`), the `config:`/`gantt:` settings Mermaid reads before the
+/// diagram body. Previously the script didn't account for frontmatter at
+/// all, so a `key: value` line inside it would be misread as a task line by
+/// `get_task_lines` (anything containing `:` and none of `MMD_GANTT_KWS`).
+use serde::{Deserialize, Serialize};
+
+/// The subset of `config.gantt` settings Mermaid documents at
+/// https://mermaid.js.org/config/configuration.html#frontmatter-config,
+/// normalized to consistent key ordering/indentation on write.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GanttConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tick_interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub today_marker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekday: Option<String>,
+    /// Any `config.gantt` key not modeled above (e.g. future Mermaid
+    /// options), kept so round-tripping doesn't drop them.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gantt: Option<GanttConfig>,
+    /// Any `config` key outside `gantt`, kept so round-tripping doesn't
+    /// drop them.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Frontmatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Config>,
+    /// Any top-level frontmatter key outside `config` (e.g. `title:`),
+    /// kept so round-tripping doesn't drop them.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Value,
+}
+
+impl Frontmatter {
+    /// The `dateFormat` override under `config.gantt`, if set. Used when the
+    /// body has no inline `dateFormat` keyword line; an inline line wins.
+    pub fn date_format_override(&self) -> Option<&str> {
+        self.config.as_ref()?.gantt.as_ref()?.date_format.as_deref()
+    }
+}
+
+/// Splits a leading `---\n...\n---\n` frontmatter block off the top of
+/// `file_text`, returning its raw YAML body (without the `---` delimiters)
+/// and the remaining document text. Returns `(None, file_text)` if the file
+/// doesn't start with a frontmatter block.
+pub fn split_frontmatter(file_text: &str) -> (Option<&str>, &str) {
+    let Some(after_open) = file_text.strip_prefix("---\n") else {
+        return (None, file_text);
+    };
+    let Some(close_idx) = after_open.find("\n---\n") else {
+        return (None, file_text);
+    };
+    let yaml = &after_open[..close_idx];
+    let body = &after_open[close_idx + "\n---\n".len()..];
+    (Some(yaml), body)
+}
+
+/// Parses a frontmatter YAML block into a [`Frontmatter`].
+pub fn parse_frontmatter(yaml: &str) -> Result<Frontmatter, serde_yaml::Error> {
+    serde_yaml::from_str(yaml)
+}
+
+/// Serializes a [`Frontmatter`] back into a `--- ... ---` block with
+/// consistent 2-space indentation and the field order declared above,
+/// regardless of how the original was written.
+pub fn render_frontmatter(frontmatter: &Frontmatter) -> Result<String, serde_yaml::Error> {
+    let yaml = serde_yaml::to_string(frontmatter)?;
+    Ok(format!("---\n{yaml}---\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_known_and_unknown_keys() {
+        let yaml = "title: My Project\nconfig:\n  gantt:\n    dateFormat: YYYY-MM-DD\n    futureOption: true\n";
+        let frontmatter = parse_frontmatter(yaml).unwrap();
+        assert_eq!(frontmatter.date_format_override(), Some("YYYY-MM-DD"));
+
+        let rendered = render_frontmatter(&frontmatter).unwrap();
+        let (reparsed_yaml, _) = split_frontmatter(&rendered);
+        let reparsed = parse_frontmatter(reparsed_yaml.unwrap()).unwrap();
+
+        assert_eq!(reparsed, frontmatter, "title and futureOption must survive the round trip");
+    }
+
+    #[test]
+    fn split_frontmatter_requires_both_delimiters() {
+        let (yaml, body) = split_frontmatter("no frontmatter here\n");
+        assert_eq!(yaml, None);
+        assert_eq!(body, "no frontmatter here\n");
+
+        let (yaml, body) = split_frontmatter("---\ntitle: unclosed\n");
+        assert_eq!(yaml, None);
+        assert_eq!(body, "---\ntitle: unclosed\n");
+    }
+}
@@ -0,0 +1,181 @@
+/// Builds a dependency graph of `after`/`until` task references and checks it
+/// for cycles and dangling references before the formatter is allowed to
+/// overwrite a file, mirroring the "no circular dependencies allowed"
+/// invariant common to dependency-tracking task tools.
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::split_meta_tags;
+use crate::TASK_TAGS;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyDiagnostic {
+    /// A back-edge was found while walking the graph; `chain` lists the ids
+    /// from the first occurrence of the repeated id back to itself, e.g.
+    /// `["a1", "a2", "a1"]`.
+    Cycle { chain: Vec<String> },
+    /// `after`/`until` referenced an id that is never declared as a task id.
+    DanglingReference { from: String, to: String },
+}
+
+impl fmt::Display for DependencyDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyDiagnostic::Cycle { chain } => {
+                write!(f, "circular dependency: {}", chain.join(" -> "))
+            }
+            DependencyDiagnostic::DanglingReference { from, to } => {
+                write!(f, "task '{from}' references unknown task id '{to}'")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    White,
+    Gray,
+    Black,
+}
+
+/// Extracts the ids referenced by a task's start (`after a b`) and end
+/// (`until a`) fields, in the order they're written.
+fn referenced_ids(task_udis: &[&str]) -> Vec<String> {
+    let mut refs = vec![];
+    let (start, end) = match task_udis.len() {
+        3 => (Some(task_udis[1]), task_udis[2]),
+        2 => (Some(task_udis[0]), task_udis[1]),
+        // A lone `after <id>` field is a point in time that starts after `<id>`.
+        1 if task_udis[0].trim().starts_with("after ") => (Some(task_udis[0]), ""),
+        1 => (None, task_udis[0]),
+        _ => return refs,
+    };
+    if let Some(start) = start {
+        if let Some(ids) = start.trim().strip_prefix("after ") {
+            refs.extend(ids.split_whitespace().map(String::from));
+        }
+    }
+    if let Some(id) = end.trim().strip_prefix("until ") {
+        refs.push(id.trim().to_string());
+    }
+    refs
+}
+
+/// Adjacency map of task id -> ids it directly depends on.
+type DependencyGraph = HashMap<String, Vec<String>>;
+/// `(referencing task id or title, referenced id)` pairs, used for the
+/// dangling-reference check.
+type ReferenceList = Vec<(String, String)>;
+
+/// Builds an adjacency map of task id -> ids it directly depends on
+/// (referenced via `after`/`until`). Tasks without an id can't be depended
+/// on and are skipped as graph nodes, but their own references are still
+/// eligible for dangling-reference checks via the returned reference list.
+fn build_dependency_graph(task_lines: &[&str]) -> (DependencyGraph, ReferenceList) {
+    let mut graph: DependencyGraph = HashMap::new();
+    let mut all_refs: ReferenceList = vec![];
+
+    for line in task_lines {
+        let task_split: Vec<&str> = line.split(':').map(str::trim).collect();
+        if task_split.len() < 2 {
+            continue;
+        }
+        let meta_items = split_meta_tags(TASK_TAGS, task_split[1]);
+        let task_udis = meta_items.get("udis").unwrap();
+        let task_id = if task_udis.len() == 3 { Some(task_udis[0]) } else { None };
+        let refs = referenced_ids(task_udis);
+
+        let from = task_id.unwrap_or(task_split[0]);
+        for referenced in &refs {
+            all_refs.push((from.to_string(), referenced.clone()));
+        }
+        if let Some(task_id) = task_id {
+            graph.entry(task_id.to_string()).or_default().extend(refs);
+        }
+    }
+
+    (graph, all_refs)
+}
+
+fn visit(
+    node: &str, graph: &HashMap<String, Vec<String>>, colors: &mut HashMap<String, Color>,
+    stack: &mut Vec<String>, cycles: &mut Vec<DependencyDiagnostic>,
+) {
+    colors.insert(node.to_string(), Color::Gray);
+    stack.push(node.to_string());
+
+    if let Some(neighbors) = graph.get(node) {
+        for neighbor in neighbors {
+            match colors.get(neighbor.as_str()).copied().unwrap_or(Color::White) {
+                Color::White => {
+                    if graph.contains_key(neighbor) {
+                        visit(neighbor, graph, colors, stack, cycles);
+                    }
+                }
+                Color::Gray => {
+                    let start = stack.iter().position(|id| id == neighbor).unwrap_or(0);
+                    let mut chain: Vec<String> = stack[start..].to_vec();
+                    chain.push(neighbor.clone());
+                    cycles.push(DependencyDiagnostic::Cycle { chain });
+                }
+                Color::Black => {}
+            }
+        }
+    }
+
+    stack.pop();
+    colors.insert(node.to_string(), Color::Black);
+}
+
+/// Runs a three-color depth-first traversal over the `after`/`until`
+/// dependency graph built from `task_lines` (as returned by
+/// `get_task_lines`), returning one diagnostic per cycle found and one per
+/// dangling reference (an id that is referenced but never declared).
+pub fn find_dependency_diagnostics(task_lines: &[&str]) -> Vec<DependencyDiagnostic> {
+    let (graph, all_refs) = build_dependency_graph(task_lines);
+    let mut diagnostics = vec![];
+
+    let mut colors: HashMap<String, Color> = HashMap::new();
+    let mut stack: Vec<String> = vec![];
+    for node in graph.keys() {
+        if colors.get(node).copied().unwrap_or(Color::White) == Color::White {
+            visit(node, &graph, &mut colors, &mut stack, &mut diagnostics);
+        }
+    }
+
+    for (from, to) in all_refs {
+        if !graph.contains_key(&to) {
+            diagnostics.push(DependencyDiagnostic::DanglingReference { from, to });
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_a_cycle() {
+        let lines = vec!["Task A :a1, after a2, 3d", "Task B :a2, after a1, 2d"];
+        let diagnostics = find_dependency_diagnostics(&lines);
+        assert!(diagnostics.iter().any(|d| matches!(d, DependencyDiagnostic::Cycle { .. })));
+    }
+
+    #[test]
+    fn detects_a_dangling_reference() {
+        let lines = vec!["Task A :a1, after missing, 3d"];
+        let diagnostics = find_dependency_diagnostics(&lines);
+        assert_eq!(
+            diagnostics,
+            vec![DependencyDiagnostic::DanglingReference { from: "a1".to_string(), to: "missing".to_string() }]
+        );
+    }
+
+    #[test]
+    fn no_diagnostics_for_a_valid_chain() {
+        let lines = vec!["Task A :a1, 2024-01-01, 3d", "Task B :a2, after a1, 2d"];
+        assert!(find_dependency_diagnostics(&lines).is_empty());
+    }
+}
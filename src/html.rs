@@ -0,0 +1,117 @@
+/// Renders a parsed gantt chart as a standalone HTML document: one row per
+/// task, grouped under its section header, with a colored bar positioned by
+/// resolved start/end date across a day grid. Lets users preview a chart
+/// without a full Mermaid renderer.
+use chrono::NaiveDate;
+
+use crate::dates::{self, DateResolutionError, ResolvedDates};
+use crate::{get_task_lines, is_task_line, split_meta_tags, TASK_TAGS};
+
+struct HtmlTask {
+    title: String,
+    tags: Vec<String>,
+    dates: ResolvedDates,
+}
+
+struct HtmlSection {
+    title: String,
+    tasks: Vec<HtmlTask>,
+}
+
+fn collect_sections(lines: &[&str]) -> Result<Vec<HtmlSection>, DateResolutionError> {
+    let (resolved_in_order, _) = dates::resolve_task_dates_in_order(lines.to_vec())?;
+    let task_lines = get_task_lines(lines.to_vec());
+    let mut resolved_iter = resolved_in_order.into_iter();
+
+    let mut sections: Vec<HtmlSection> = vec![];
+    for raw_line in lines {
+        let line = raw_line.trim();
+        if let Some(section_title) = line.strip_prefix("section") {
+            sections.push(HtmlSection { title: section_title.trim().to_string(), tasks: vec![] });
+        } else if is_task_line(line) && task_lines.contains(&line) {
+            let Some(task_dates) = resolved_iter.next().expect("one slot per task line") else {
+                // Malformed line (0 or 4+ UDI fields): no dates to position
+                // a bar with, so it can't be rendered as a row.
+                continue;
+            };
+            let task_split: Vec<&str> = line.splitn(2, ':').map(str::trim).collect();
+            if task_split.len() < 2 {
+                continue;
+            }
+            let meta_items = split_meta_tags(TASK_TAGS, task_split[1]);
+            let tags: Vec<String> = TASK_TAGS
+                .iter()
+                .filter(|tag| meta_items.get("tags").unwrap().contains(tag))
+                .map(|tag| tag.to_string())
+                .collect();
+            let task = HtmlTask { title: task_split[0].to_string(), tags, dates: task_dates };
+            match sections.last_mut() {
+                Some(section) => section.tasks.push(task),
+                None => sections.push(HtmlSection { title: String::new(), tasks: vec![task] }),
+            }
+        }
+    }
+    Ok(sections)
+}
+
+fn css_classes(task: &HtmlTask) -> String {
+    let mut classes = vec!["task-bar"];
+    for tag in &task.tags {
+        classes.push(tag.as_str());
+    }
+    classes.join(" ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2rem; }
+.section-title { font-weight: bold; margin-top: 1.5rem; }
+.task-row { display: flex; align-items: center; height: 1.8rem; }
+.task-label { width: 14rem; flex-shrink: 0; font-size: 0.9rem; }
+.task-track { position: relative; flex-grow: 1; height: 1.2rem; background: #f0f0f0; }
+.task-bar { position: absolute; top: 0; height: 100%; border-radius: 3px; background: #8ab4f8; }
+.task-bar.done { background: #9e9e9e; }
+.task-bar.active { background: #4caf50; }
+.task-bar.crit { outline: 2px solid #d32f2f; }
+.task-bar.milestone { width: 0.9rem !important; height: 0.9rem; top: 0.15rem; transform: rotate(45deg); border-radius: 2px; background: #ff9800; }
+"#;
+
+/// Renders the gantt diagram in `lines` to a standalone HTML document,
+/// one row per task grouped under its section, with bars positioned across
+/// a day grid spanning the full resolved date range.
+pub fn render_html(lines: Vec<&str>) -> Result<String, DateResolutionError> {
+    let sections = collect_sections(&lines)?;
+
+    let all_dates: Vec<NaiveDate> =
+        sections.iter().flat_map(|s| s.tasks.iter().flat_map(|t| [t.dates.start, t.dates.end])).collect();
+    let range_start = all_dates.iter().min().copied().unwrap_or_default();
+    let range_end = all_dates.iter().max().copied().unwrap_or_default();
+    let total_days = (range_end - range_start).num_days().max(1) as f64;
+
+    let mut body = String::new();
+    for section in &sections {
+        if !section.title.is_empty() {
+            body.push_str(&format!("<div class=\"section-title\">{}</div>\n", escape_html(&section.title)));
+        }
+        for task in &section.tasks {
+            let offset_days = (task.dates.start - range_start).num_days() as f64;
+            let span_days = (task.dates.end - task.dates.start).num_days().max(1) as f64;
+            let left_pct = offset_days / total_days * 100.0;
+            let width_pct = span_days / total_days * 100.0;
+            body.push_str(&format!(
+                "<div class=\"task-row\"><div class=\"task-label\">{label}</div><div class=\"task-track\"><div class=\"{class}\" style=\"left: {left:.2}%; width: {width:.2}%;\"></div></div></div>\n",
+                label = escape_html(&task.title),
+                class = css_classes(task),
+                left = left_pct,
+                width = width_pct,
+            ));
+        }
+    }
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    ))
+}
@@ -22,9 +22,16 @@ use std::ops::Add;
 use std::str::Split;
 use std::vec::IntoIter;
 
+mod dates;
+mod frontmatter;
+mod graph;
+mod html;
+mod query;
+mod validate;
+
 /// Required/optional keywords that may appear at the top of a mermaid gantt file or elsewhere.
 /// Note: this is not an exhaustive list. This script doesn't currently account for YAML frontmatter. https://mermaid.js.org/config/configuration.html#frontmatter-config
-const MMD_GANTT_KWS: [&str; 23] = [
+pub(crate) const MMD_GANTT_KWS: [&str; 23] = [
     "axisFormat",
     "barGap",
     "barHeight",
@@ -51,7 +58,7 @@ const MMD_GANTT_KWS: [&str; 23] = [
 ];
 
 /// Optional metadata tags available.
-const TASK_TAGS: [&str; 4] = ["done", "active", "crit", "milestone"];
+pub(crate) const TASK_TAGS: [&str; 4] = ["done", "active", "crit", "milestone"];
 
 /// udis = user defined items.
 /// These are referring to metadata tags outside of the default tags [active, done, crit, and milestone].
@@ -143,20 +150,16 @@ fn push_tags_to_task_line(mut task_line: String, task_tags: &Vec<&str>) -> Strin
     return task_line;
 }
 
-fn get_task_lines(lines: Vec<&str>) -> Vec<&str> {
+/// A line is a task line if it contains none of `MMD_GANTT_KWS` (anywhere
+/// in the line), isn't a comment, and does carry the `:` that separates a
+/// task title from its metadata.
+pub(crate) fn is_task_line(line: &str) -> bool {
+    !MMD_GANTT_KWS.iter().any(|&tag| line.contains(tag)) && !line.starts_with("%%") && line.contains(':')
+}
+
+pub(crate) fn get_task_lines(lines: Vec<&str>) -> Vec<&str> {
     let mut task_lines: Vec<&str> = vec![];
-    for line in lines
-        .iter()
-        .cloned()
-        .map(str::trim)
-        .filter(|&line| {
-            !MMD_GANTT_KWS
-                .iter()
-                .any(|&tag| line.contains(tag))
-                && !line.starts_with("%%")
-                && line.contains(":")
-        })
-    {
+    for line in lines.iter().cloned().map(str::trim).filter(|&line| is_task_line(line)) {
         task_lines.push(line);
     }
     return task_lines;
@@ -267,7 +270,7 @@ fn get_max_item_lengths<'a>(tags: [&str; 4], lines: Vec<&'a str>) -> HashMap<&'a
     return map_item_lenths;
 }
 
-fn split_meta_tags<'a>(tags: [&str; 4], metadata: &'a str) -> HashMap<String, Vec<&'a str>> {
+pub(crate) fn split_meta_tags<'a>(tags: [&str; 4], metadata: &'a str) -> HashMap<String, Vec<&'a str>> {
     let meta_items: Map<Split<'_, &str>, fn(&str) -> &str> = metadata.split(",").map(str::trim);
     let task_tags: Vec<&str> = meta_items
         .clone()
@@ -378,26 +381,202 @@ fn generate_new_lines(lines: Vec<&str>) -> Vec<String> {
     return new_lines;
 }
 
+/// Rewrites every task's start and end fields (`after <id>`/`until <id>`
+/// references and durations alike) into literal dates (formatted with the
+/// document's own `dateFormat`), using the
+/// concrete dates computed by [`dates::resolve_task_dates_in_order`]. Leaves
+/// everything else (tags, ids, titles) untouched so the result can still be
+/// run through `generate_new_lines` for alignment.
+fn resolve_relative_dates(file_text: &str) -> Result<String, dates::DateResolutionError> {
+    let lines: Vec<&str> = file_text.lines().collect();
+    let chrono_date_format = dates::resolve_date_format(&lines);
+    let (resolved_in_order, _) = dates::resolve_task_dates_in_order(lines.clone())?;
+    let task_lines = get_task_lines(lines.clone());
+
+    let mut resolved_iter = resolved_in_order.into_iter();
+    let mut new_lines: Vec<String> = vec![];
+    for line in lines {
+        let trimmed = line.trim();
+        if task_lines.contains(&trimmed) {
+            let Some(resolved) = resolved_iter.next().expect("one slot per task line") else {
+                // Malformed line (0 or 4+ UDI fields): nothing to resolve,
+                // pass it through unchanged.
+                new_lines.push(String::from(line));
+                continue;
+            };
+            let task_split: Vec<&str> = line.split(':').collect();
+            let meta_items = split_meta_tags(TASK_TAGS, task_split[1]);
+            let task_udis = meta_items.get("udis").unwrap();
+            let start = dates::format_date(resolved.start, &chrono_date_format);
+            let end = dates::format_date(resolved.end, &chrono_date_format);
+            let udis = match task_udis.len() {
+                3 => format!("{}, {}, {}", task_udis[0], start, end),
+                2 => format!("{}, {}", start, end),
+                1 => end,
+                _ => task_split[1].trim().to_string(),
+            };
+            let tags: Vec<&str> =
+                meta_items.get("tags").unwrap().iter().filter(|tag| TASK_TAGS.contains(tag)).cloned().collect();
+            let mut metadata = tags.join(", ");
+            if !metadata.is_empty() {
+                metadata.push_str(", ");
+            }
+            metadata.push_str(&udis);
+            new_lines.push(format!("{}: {}", task_split[0], metadata));
+        } else {
+            new_lines.push(String::from(line));
+        }
+    }
+    Ok(new_lines.join("\n"))
+}
+
+/// Checks the `after`/`until` dependency graph for cycles and dangling
+/// references, printing one line per diagnostic. Returns `true` if any were
+/// found, so the caller can refuse to overwrite the file.
+fn report_dependency_diagnostics(file_text: &str) -> bool {
+    let lines: Vec<&str> = file_text.lines().collect();
+    let task_lines = get_task_lines(lines);
+    let diagnostics = graph::find_dependency_diagnostics(&task_lines);
+    for diagnostic in &diagnostics {
+        eprintln!("error: {diagnostic}");
+    }
+    !diagnostics.is_empty()
+}
+
+/// Runs the `--check` invariant validation, printing one line per
+/// diagnostic. Returns `true` if any were found.
+fn report_validation_diagnostics(file_text: &str) -> bool {
+    let lines: Vec<&str> = file_text.lines().collect();
+    let diagnostics = validate::check_gantt(&lines);
+    for diagnostic in &diagnostics {
+        eprintln!("error: {diagnostic}");
+    }
+    !diagnostics.is_empty()
+}
+
+/// Takes the value of a `--flag=value` argument out of `args`, if present.
+fn take_flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let prefix = format!("{flag}=");
+    let idx = args.iter().position(|arg| arg.starts_with(&prefix))?;
+    Some(args.remove(idx).strip_prefix(&prefix).unwrap().to_string())
+}
+
+/// Parses the `--filter-from=`/`--filter-to=` date window, if both are
+/// given, using the document's own `dateFormat`.
+fn parse_filter_window(
+    from: Option<&String>, to: Option<&String>, chrono_date_format: &str,
+) -> Option<(chrono::NaiveDate, chrono::NaiveDate)> {
+    let from = chrono::NaiveDate::parse_from_str(from?, chrono_date_format).ok()?;
+    let to = chrono::NaiveDate::parse_from_str(to?, chrono_date_format).ok()?;
+    Some((from, to))
+}
+
 /// First arg = file to read.
 /// Second arg = file to write to.
 /// If only the first arg is provided, then file is edited in-place.
+/// A `--resolve` flag (anywhere in the args) rewrites every task's start/end,
+/// `after`/`until` references and durations included, into absolute dates
+/// before formatting. A `--check` flag
+/// validates gantt invariants and exits non-zero without formatting,
+/// printing each violation with its line number (safe for a pre-commit
+/// hook). `--sort=start|id|title`, `--filter-tag=<tag>`, and
+/// `--filter-from=<date>`/`--filter-to=<date>` reorder and/or filter the
+/// tasks within each section.
+///
+/// Before writing, the `after`/`until` dependency graph is checked for
+/// cycles and dangling references; if any are found, the file is left
+/// untouched and the process exits non-zero.
 fn main() -> std::io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+    let mut all_args: Vec<String> = std::env::args().collect();
+    let check = all_args.iter().any(|arg| arg == "--check");
+    let resolve = all_args.iter().any(|arg| arg == "--resolve");
+    let sort = take_flag_value(&mut all_args, "--sort").and_then(|value| query::SortKey::parse(&value));
+    let filter_tag = take_flag_value(&mut all_args, "--filter-tag");
+    let filter_from = take_flag_value(&mut all_args, "--filter-from");
+    let filter_to = take_flag_value(&mut all_args, "--filter-to");
+    let args: Vec<String> =
+        all_args.into_iter().filter(|arg| arg != "--resolve" && arg != "--check").collect();
+
     let file_name: &String = &args[1];
     let file_text: String =
         std::fs::read_to_string(file_name).expect(&format!("Could not read file {}", file_name));
 
+    let (raw_frontmatter, body) = frontmatter::split_frontmatter(&file_text);
+    // Lines taken up by the frontmatter block, so `--check` can report line
+    // numbers of the file as written.
+    let frontmatter_line_count = file_text[..file_text.len() - body.len()].lines().count();
+    let raw_body = body;
+    let frontmatter = raw_frontmatter
+        .map(frontmatter::parse_frontmatter)
+        .transpose()
+        .unwrap_or_else(|err| panic!("Failed to parse YAML frontmatter in {}: {}", file_name, err));
+    // A frontmatter `dateFormat` override is used when the body has no
+    // inline keyword line (an inline line, when present, wins); inject it as
+    // one so every existing consumer that scans
+    // for the `dateFormat` keyword line picks it up without extra plumbing.
+    let body = match frontmatter.as_ref().and_then(frontmatter::Frontmatter::date_format_override) {
+        Some(date_format) if !body.lines().any(|line| line.trim().starts_with("dateFormat")) => {
+            body.replacen('\n', &format!("\n  dateFormat {date_format}\n"), 1)
+        }
+        _ => body.to_string(),
+    };
+
+    if check {
+        // Validate the body as written, behind one blank line per frontmatter
+        // line, so reported line numbers are those of the file itself.
+        let check_text = format!("{}{}", "\n".repeat(frontmatter_line_count), raw_body);
+        if report_validation_diagnostics(&check_text) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if report_dependency_diagnostics(&body) {
+        std::process::exit(1);
+    }
+
+    let body = if resolve {
+        resolve_relative_dates(&body).unwrap_or_else(|err| panic!("Failed to resolve dates in {}: {}", file_name, err))
+    } else {
+        body
+    };
+
+    let has_query = sort.is_some() || filter_tag.is_some() || (filter_from.is_some() && filter_to.is_some());
+    let body = if has_query {
+        let lines: Vec<&str> = body.lines().collect();
+        let chrono_date_format = dates::resolve_date_format(&lines);
+        let filter_window = parse_filter_window(filter_from.as_ref(), filter_to.as_ref(), &chrono_date_format);
+        query::apply_query(lines, sort, filter_tag.as_deref(), filter_window)
+            .unwrap_or_else(|err| panic!("Failed to apply sort/filter to {}: {}", file_name, err))
+    } else {
+        body
+    };
+
+    let rendered_frontmatter = frontmatter
+        .as_ref()
+        .map(|fm| {
+            frontmatter::render_frontmatter(fm)
+                .unwrap_or_else(|err| panic!("Failed to render YAML frontmatter for {}: {}", file_name, err))
+        })
+        .unwrap_or_default();
+
     match &args.len() {
         3 => {
             let destination: &String = &args[2];
-            create_or_replace_file(
-                destination,
-                generate_new_lines(file_text.lines().collect()).join("\n"),
-            )
+            if destination.ends_with(".html") {
+                let rendered = html::render_html(body.lines().collect())
+                    .unwrap_or_else(|err| panic!("Failed to render HTML for {}: {}", file_name, err));
+                create_or_replace_file(destination, rendered)
+            } else {
+                create_or_replace_file(
+                    destination,
+                    format!("{}{}", rendered_frontmatter, generate_new_lines(body.lines().collect()).join("\n")),
+                )
+            }
         }
         _ => create_or_replace_file(
             file_name,
-            generate_new_lines(file_text.lines().collect()).join("\n"),
+            format!("{}{}", rendered_frontmatter, generate_new_lines(body.lines().collect()).join("\n")),
         ),
     }
 }
@@ -0,0 +1,145 @@
+/// Optional reordering and filtering of tasks within each section, borrowing
+/// the `::PROP` sort / tag-filter ideas from task CLIs. Because Mermaid
+/// tasks are positionally sequential (a task with no start date inherits the
+/// previous task's end), reordering first resolves absolute dates (see
+/// `dates`) and then rewrites every task's start/end as an explicit literal
+/// date, so the now-reordered chart still renders identically and no
+/// `after`/`until` reference can end up dangling after filtering.
+use chrono::NaiveDate;
+
+use crate::dates::{self, DateResolutionError, ResolvedDates};
+use crate::{get_task_lines, is_task_line, split_meta_tags, TASK_TAGS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Start,
+    Id,
+    Title,
+}
+
+impl SortKey {
+    pub fn parse(value: &str) -> Option<SortKey> {
+        match value {
+            "start" => Some(SortKey::Start),
+            "id" => Some(SortKey::Id),
+            "title" => Some(SortKey::Title),
+            _ => None,
+        }
+    }
+}
+
+struct QueryTask {
+    title: String,
+    id: Option<String>,
+    tags: Vec<String>,
+    dates: ResolvedDates,
+}
+
+fn parse_task(line: &str, dates: ResolvedDates) -> Option<QueryTask> {
+    let task_split: Vec<&str> = line.splitn(2, ':').map(str::trim).collect();
+    if task_split.len() < 2 {
+        return None;
+    }
+    let meta_items = split_meta_tags(TASK_TAGS, task_split[1]);
+    let tags: Vec<String> =
+        TASK_TAGS.iter().filter(|tag| meta_items.get("tags").unwrap().contains(tag)).map(|tag| tag.to_string()).collect();
+    let task_udis = meta_items.get("udis").unwrap();
+    let id = if task_udis.len() == 3 { Some(task_udis[0].to_string()) } else { None };
+    Some(QueryTask { title: task_split[0].to_string(), id, tags, dates })
+}
+
+fn render_task(task: &QueryTask, chrono_date_format: &str) -> String {
+    let mut metadata = task.tags.join(", ");
+    if !metadata.is_empty() {
+        metadata.push_str(", ");
+    }
+    if let Some(id) = &task.id {
+        metadata.push_str(id);
+        metadata.push_str(", ");
+    }
+    metadata.push_str(&dates::format_date(task.dates.start, chrono_date_format));
+    metadata.push_str(", ");
+    metadata.push_str(&dates::format_date(task.dates.end, chrono_date_format));
+    format!("{}: {}", task.title, metadata)
+}
+
+/// A section's contents in source order: either a task (subject to
+/// filtering/sorting) or any other line (comment, blank, keyword), which
+/// keeps its original position relative to the tasks around it.
+enum SectionItem {
+    Task(QueryTask),
+    Line(String),
+}
+
+/// Rewrites `lines` with each section's tasks optionally filtered by tag
+/// and/or date window, and optionally sorted, with every task's start/end
+/// rewritten to an explicit literal date. Non-task lines (comments, blanks)
+/// stay at their original position relative to the tasks around them — only
+/// the tasks themselves are reordered/dropped. The result still needs to be
+/// run through `generate_new_lines` for column alignment.
+pub fn apply_query(
+    lines: Vec<&str>, sort: Option<SortKey>, filter_tag: Option<&str>, filter_window: Option<(NaiveDate, NaiveDate)>,
+) -> Result<String, DateResolutionError> {
+    let chrono_date_format = dates::resolve_date_format(&lines);
+    let (resolved_in_order, _) = dates::resolve_task_dates_in_order(lines.clone())?;
+    let task_lines = get_task_lines(lines.clone());
+    let mut resolved_iter = resolved_in_order.into_iter();
+
+    let mut new_lines: Vec<String> = vec![];
+    let mut current_section: Vec<SectionItem> = vec![];
+
+    let flush_section = |section: &mut Vec<SectionItem>, new_lines: &mut Vec<String>| {
+        let mut tasks: Vec<&QueryTask> = section
+            .iter()
+            .filter_map(|item| match item {
+                SectionItem::Task(task) => Some(task),
+                SectionItem::Line(_) => None,
+            })
+            .filter(|task| filter_tag.is_none_or(|tag| task.tags.iter().any(|t| t == tag)))
+            .filter(|task| filter_window.is_none_or(|(from, to)| task.dates.start >= from && task.dates.end <= to))
+            .collect();
+        match sort {
+            Some(SortKey::Start) => tasks.sort_by_key(|task| task.dates.start),
+            Some(SortKey::Id) => tasks.sort_by_key(|task| task.id.clone().unwrap_or_default()),
+            Some(SortKey::Title) => tasks.sort_by_key(|task| task.title.clone()),
+            None => {}
+        }
+
+        // Replay the section in its original shape: each task slot is
+        // filled from the filtered/sorted list in order (dropped if it was
+        // filtered out), while every other line stays exactly where it was.
+        let mut sorted_tasks = tasks.into_iter();
+        for item in section.iter() {
+            match item {
+                SectionItem::Task(_) => {
+                    if let Some(task) = sorted_tasks.next() {
+                        new_lines.push(format!("    {}", render_task(task, &chrono_date_format)));
+                    }
+                }
+                SectionItem::Line(line) => new_lines.push(line.clone()),
+            }
+        }
+        section.clear();
+    };
+
+    for raw_line in lines {
+        let line = raw_line.trim();
+        if let Some(section_title) = line.strip_prefix("section") {
+            flush_section(&mut current_section, &mut new_lines);
+            new_lines.push(format!("\n  section {}", section_title.trim()));
+        } else if is_task_line(line) && task_lines.contains(&line) {
+            let task_dates = resolved_iter.next().expect("one slot per task line");
+            match task_dates.and_then(|dates| parse_task(line, dates)) {
+                Some(task) => current_section.push(SectionItem::Task(task)),
+                // Malformed line (0/4+ UDIs, or an unparseable split): can't
+                // be dated/sorted, so pass it through unchanged.
+                None => current_section.push(SectionItem::Line(String::from(line))),
+            }
+        } else {
+            current_section.push(SectionItem::Line(String::from(line)));
+        }
+    }
+    flush_section(&mut current_section, &mut new_lines);
+
+    Ok(new_lines.join("\n"))
+}
@@ -0,0 +1,219 @@
+/// A `--check` mode that parses the input and reports invariant violations
+/// instead of formatting, so the formatter is safe to run in a pre-commit
+/// hook: duplicate task ids, unrecognized tags, conflicting `active`/`done`
+/// tags, nonsensical `milestone` durations, too many UDI fields, and
+/// malformed duration tokens.
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::is_task_line;
+use crate::TASK_TAGS;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationDiagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Tag,
+    Reference,
+    Duration,
+    Date,
+    Id,
+    Unknown,
+}
+
+fn is_duration_token(token: &str) -> bool {
+    duration_amount(token).is_some()
+}
+
+/// Parses a duration token's numeric amount (ignoring its `d`/`w` unit), so
+/// callers can check e.g. "is this duration actually zero-length" rather
+/// than just "is this a duration".
+fn duration_amount(token: &str) -> Option<i64> {
+    let token = token.trim();
+    token.strip_suffix(['d', 'w']).and_then(|count| count.parse::<i64>().ok())
+}
+
+/// Levenshtein (edit) distance between two strings, used to flag a tag-prefix
+/// token that's probably a misspelled tag rather than a deliberate task id.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev_diag
+            } else {
+                1 + prev_diag.min(row[j]).min(row[j + 1])
+            };
+            prev_diag = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// A token that isn't itself a recognized tag, but is one edit away from one
+/// (e.g. `activ` vs `active`), is almost certainly a misspelled tag rather
+/// than a deliberate task id — unlike an unrelated word such as `design`.
+fn likely_tag_typo(token: &str) -> Option<&'static str> {
+    TASK_TAGS.iter().copied().find(|&tag| edit_distance(token, tag) <= 1)
+}
+
+fn classify(token: &str) -> TokenKind {
+    let token = token.trim();
+    if TASK_TAGS.contains(&token) {
+        TokenKind::Tag
+    } else if token.starts_with("after ") || token.starts_with("until ") {
+        TokenKind::Reference
+    } else if is_duration_token(token) {
+        TokenKind::Duration
+    } else if token.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        TokenKind::Date
+    } else if token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        TokenKind::Id
+    } else {
+        TokenKind::Unknown
+    }
+}
+
+/// Splits a task's raw metadata (the text after the `:`) into comma
+/// separated, trimmed, non-empty tokens, preserving order.
+fn metadata_tokens(metadata: &str) -> Vec<&str> {
+    metadata.split(',').map(str::trim).filter(|token| !token.is_empty()).collect()
+}
+
+fn check_task_line(line_no: usize, line: &str, seen_ids: &mut HashSet<String>, diagnostics: &mut Vec<ValidationDiagnostic>) {
+    let task_split: Vec<&str> = line.splitn(2, ':').map(str::trim).collect();
+    if task_split.len() < 2 {
+        return;
+    }
+    let tokens = metadata_tokens(task_split[1]);
+
+    let mut in_tag_section = true;
+    let mut tags: Vec<&str> = vec![];
+    let mut udis: Vec<&str> = vec![];
+    for &token in &tokens {
+        // The tag section is a strict prefix: the first token that isn't a
+        // recognized tag ends it. That token is usually a deliberate task id
+        // (e.g. `design`), but if it's one edit away from a known tag (e.g.
+        // `activ`), it's almost certainly a typo, so flag it before treating
+        // it as the id.
+        if !in_tag_section {
+            udis.push(token);
+            continue;
+        }
+        if classify(token) == TokenKind::Tag {
+            tags.push(token);
+            continue;
+        }
+        if let Some(intended) = likely_tag_typo(token) {
+            diagnostics.push(ValidationDiagnostic {
+                line: line_no,
+                message: format!("'{token}' is not a recognized tag (did you mean '{intended}'? valid tags: {})", TASK_TAGS.join(", ")),
+            });
+        }
+        in_tag_section = false;
+        udis.push(token);
+    }
+
+    if tags.contains(&"active") && tags.contains(&"done") {
+        diagnostics.push(ValidationDiagnostic {
+            line: line_no,
+            message: String::from("task cannot be both 'active' and 'done'"),
+        });
+    }
+
+    // A milestone marks a point in time: `id, date, 0d` (or `date, 0d`) is
+    // the documented way to spell that, since the duration field is
+    // otherwise required by the general task shape but must be zero-length.
+    // What doesn't make sense is a *nonzero* duration, or a second literal
+    // date instead of a duration — either would make it a real span.
+    if tags.contains(&"milestone") {
+        let trailing_field = match udis.len() {
+            3 => Some(udis[2]),
+            2 => Some(udis[1]),
+            _ => None,
+        };
+        if let Some(field) = trailing_field {
+            if duration_amount(field.trim()) != Some(0) {
+                diagnostics.push(ValidationDiagnostic {
+                    line: line_no,
+                    message: format!(
+                        "'milestone' tasks mark a point in time: trailing field '{}' must be a 0d duration, not a second date or nonzero span",
+                        field.trim()
+                    ),
+                });
+            }
+        }
+    }
+
+    if udis.len() > 3 {
+        diagnostics.push(ValidationDiagnostic {
+            line: line_no,
+            message: format!("{} metadata items after tags, expected at most 3 (id, start, end)", udis.len()),
+        });
+    }
+
+    if udis.len() == 3 {
+        let id = udis[0];
+        if !seen_ids.insert(id.to_string()) {
+            diagnostics.push(ValidationDiagnostic {
+                line: line_no,
+                message: format!("duplicate task id '{id}'"),
+            });
+        }
+    }
+
+    let end = match udis.len() {
+        3 => Some(udis[2]),
+        2 => Some(udis[1]),
+        1 => Some(udis[0]),
+        _ => None,
+    };
+    if let Some(end) = end {
+        let end_trimmed = end.trim();
+        // A lone field with no start (e.g. a milestone's `after a2`) is a
+        // point-in-time reference, not a duration/end-date.
+        let is_solo_reference = udis.len() == 1 && end_trimmed.starts_with("after ");
+        let is_valid = is_solo_reference
+            || end_trimmed.starts_with("until ")
+            || is_duration_token(end_trimmed)
+            || end_trimmed.chars().next().is_some_and(|c| c.is_ascii_digit());
+        if !is_valid {
+            diagnostics.push(ValidationDiagnostic {
+                line: line_no,
+                message: format!("malformed duration or end date '{end_trimmed}'"),
+            });
+        }
+    }
+}
+
+/// Parses `lines` (the full file, 0-indexed) and returns one diagnostic per
+/// invariant violation, tagged with its 1-indexed line number. An empty
+/// result means the file is safe to format/overwrite.
+pub fn check_gantt(lines: &[&str]) -> Vec<ValidationDiagnostic> {
+    let mut diagnostics = vec![];
+    let mut seen_ids: HashSet<String> = HashSet::new();
+
+    for (idx, raw_line) in lines.iter().enumerate() {
+        let line = raw_line.trim();
+        if is_task_line(line) {
+            check_task_line(idx + 1, line, &mut seen_ids, &mut diagnostics);
+        }
+    }
+
+    diagnostics
+}